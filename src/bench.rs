@@ -0,0 +1,185 @@
+//! Benchmarking harness for comparing sequential vs concurrent scaling.
+//!
+//! Turns the ad-hoc `Instant` timing stashed in [`CountOutput`](crate::CountOutput)
+//! into a reusable measurement API: run a counting strategy over a corpus across
+//! a sweep of thread counts, repeat each run, and report throughput and speedup.
+
+use crate::{count_multiple_concurrent, count_multiple_seq, FastyResult};
+use std::fs;
+
+/// Timing for one counting strategy at one thread count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchResult {
+    pub label: String,
+    pub threads: usize,
+    pub bytes: u64,
+    /// Best (minimum) elapsed time across the repeated runs, in microseconds.
+    pub elapsed_micros: u128,
+    pub runs: u32,
+}
+
+impl BenchResult {
+    /// Derived throughput in megabytes per second.
+    pub fn throughput_mb_s(&self) -> f64 {
+        if self.elapsed_micros == 0 {
+            return 0.0;
+        }
+        // bytes / 1e6 over micros / 1e6 cancels to bytes / micros.
+        self.bytes as f64 / self.elapsed_micros as f64
+    }
+}
+
+/// A full sweep: the sequential baseline plus concurrent runs per thread count.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub sequential: BenchResult,
+    pub concurrent: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    /// Speedup of `result` over the sequential baseline (higher is better).
+    pub fn speedup(&self, result: &BenchResult) -> f64 {
+        if result.elapsed_micros == 0 {
+            return 0.0;
+        }
+        self.sequential.elapsed_micros as f64 / result.elapsed_micros as f64
+    }
+
+    /// Render the report as a readable table with thousands-separated counts.
+    pub fn format(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "corpus: {} bytes, {} runs each\n",
+            with_thousands(self.sequential.bytes),
+            self.sequential.runs
+        ));
+        out.push_str("strategy             threads   elapsed(us)      MB/s   speedup\n");
+        out.push_str(&self.row(&self.sequential));
+        for result in &self.concurrent {
+            out.push_str(&self.row(result));
+        }
+        out
+    }
+
+    fn row(&self, result: &BenchResult) -> String {
+        format!(
+            "{:<18}  {:>7}  {:>12}  {:>8.2}  {:>6.2}x\n",
+            result.label,
+            result.threads,
+            with_thousands(result.elapsed_micros as u64),
+            result.throughput_mb_s(),
+            self.speedup(result),
+        )
+    }
+}
+
+/// Group a number's digits into thousands with commas (e.g. `1,234,567`).
+fn with_thousands(n: u64) -> String {
+    let digits: Vec<char> = n.to_string().chars().collect();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.iter().enumerate() {
+        if i != 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(*c);
+    }
+    out
+}
+
+/// Sum the on-disk size of every path in the corpus.
+fn corpus_bytes(file_paths: &[String]) -> u64 {
+    file_paths
+        .iter()
+        .map(|path| fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+        .sum()
+}
+
+/// Run the sequential baseline once per repeat, keeping the best elapsed time.
+fn bench_sequential(
+    file_paths: &[String],
+    alphabets: &[char],
+    bytes: u64,
+    repeats: u32,
+) -> FastyResult<BenchResult> {
+    let mut best = u128::MAX;
+    for _ in 0..repeats.max(1) {
+        best = best.min(count_multiple_seq(file_paths, alphabets)?.elapsed);
+    }
+    Ok(BenchResult {
+        label: "sequential".to_string(),
+        threads: 1,
+        bytes,
+        elapsed_micros: best,
+        runs: repeats.max(1),
+    })
+}
+
+/// Run the concurrent strategy at `threads`, keeping the best elapsed time.
+fn bench_concurrent(
+    file_paths: &[String],
+    alphabets: &[char],
+    bytes: u64,
+    threads: usize,
+    repeats: u32,
+) -> FastyResult<BenchResult> {
+    let mut best = u128::MAX;
+    for _ in 0..repeats.max(1) {
+        let output =
+            count_multiple_concurrent(file_paths.to_vec(), alphabets.to_vec(), Some(threads))?;
+        best = best.min(output.elapsed);
+    }
+    Ok(BenchResult {
+        label: "concurrent".to_string(),
+        threads,
+        bytes,
+        elapsed_micros: best,
+        runs: repeats.max(1),
+    })
+}
+
+/// Benchmark sequential vs concurrent counting over `thread_counts`, repeating
+/// each measurement `repeats` times and reporting the best run.
+pub fn run(
+    file_paths: &[String],
+    alphabets: &[char],
+    thread_counts: &[usize],
+    repeats: u32,
+) -> FastyResult<BenchReport> {
+    let bytes = corpus_bytes(file_paths);
+    let sequential = bench_sequential(file_paths, alphabets, bytes, repeats)?;
+    let concurrent = thread_counts
+        .iter()
+        .map(|&threads| bench_concurrent(file_paths, alphabets, bytes, threads, repeats))
+        .collect::<FastyResult<Vec<_>>>()?;
+    Ok(BenchReport {
+        sequential,
+        concurrent,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lipsum::lipsum_words;
+
+    #[test]
+    fn should_report_scaling_across_thread_counts() {
+        // Arrange
+        let file_paths: Vec<String> = (0..8).map(|num| format!("data/bench{num}.txt")).collect();
+        file_paths.iter().for_each(|file_path| {
+            fs::write(file_path, lipsum_words(5000)).unwrap();
+        });
+        let alphabets = vec!['a', 'c'];
+        // Act
+        let report = run(&file_paths, &alphabets, &[1, 2, 4], 2).unwrap();
+        // remove generated txt files
+        file_paths.iter().for_each(|file_path| {
+            fs::remove_file(file_path).unwrap();
+        });
+        // Assert
+        assert_eq!(3, report.concurrent.len());
+        assert!(report.sequential.bytes > 0);
+        assert_eq!(2, report.sequential.runs);
+        assert!(report.format().contains("speedup"));
+    }
+}
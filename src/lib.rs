@@ -1,13 +1,160 @@
-use std::{fs, io, sync::Arc, thread, time::Instant};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs, io,
+    os::unix::fs::MetadataExt,
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+pub mod bench;
 
 /// Custom result type alias
 pub type FastyResult<T> = Result<T, io::Error>;
 
+/// Byte window used when streaming a file chunk-by-chunk.
+const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
+
+/// Below this length a single file is counted sequentially; above it the
+/// per-chunk work is spread across the rayon pool. Borrowed from the BLAKE3
+/// heuristic so thread-spawn overhead doesn't dominate on small inputs.
+const PARALLEL_THRESHOLD: u64 = 4 << 20; // 4 MiB
+
 /// Count the match counts of alphabets inside the imported file.
+///
+/// The file is memory-mapped and scanned over byte windows so multi-gigabyte,
+/// possibly non-UTF-8 files work with bounded memory. Large files fan out over
+/// the rayon pool; small ones stay sequential.
 pub fn count_alpha(file_path: &str, alphabets: &[char]) -> FastyResult<u128> {
+    let file = fs::File::open(file_path)?;
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(0);
+    }
+    // Safety: the file stays open for the lifetime of the mapping.
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    if len > PARALLEL_THRESHOLD {
+        count_bytes_parallel(&mmap, alphabets)
+    } else {
+        Ok(count_bytes_sequential(&mmap, alphabets))
+    }
+}
+
+/// Count a single file without any inner fan-out. Used by the multi-file
+/// concurrent paths, which already fan out over files, so per-file counting
+/// stays sequential and doesn't oversubscribe the rayon pool.
+fn count_alpha_seq(file_path: &str, alphabets: &[char]) -> FastyResult<u128> {
+    let file = fs::File::open(file_path)?;
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(0);
+    }
+    // Safety: the file stays open for the lifetime of the mapping.
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(count_bytes_sequential(&mmap, alphabets))
+}
+
+/// Count matches of `alphabets` within a byte slice, returning the count and any
+/// trailing bytes that form an incomplete UTF-8 sequence to carry into the next
+/// chunk. Invalid (non-boundary) bytes are skipped.
+fn count_in_bytes<'a>(mut bytes: &'a [u8], alphabets: &[char]) -> (u128, &'a [u8]) {
+    let mut count = 0u128;
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => {
+                count += text.matches(alphabets).count() as u128;
+                return (count, &[]);
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                // Safety: `valid_up_to` guarantees this prefix is valid UTF-8.
+                let text = unsafe { std::str::from_utf8_unchecked(&bytes[..valid]) };
+                count += text.matches(alphabets).count() as u128;
+                match e.error_len() {
+                    // Incomplete multi-byte sequence at the end: carry it over.
+                    None => return (count, &bytes[valid..]),
+                    // Genuinely invalid bytes: skip them and keep scanning. Loop
+                    // rather than recurse so a fully non-UTF-8 chunk stays O(1)
+                    // in stack depth.
+                    Some(bad) => bytes = &bytes[valid + bad..],
+                }
+            }
+        }
+    }
+}
+
+/// Stream a byte slice in [`CHUNK_SIZE`] windows, carrying the tail of one window
+/// into the next so a multi-byte character straddling the seam isn't miscounted.
+fn count_bytes_sequential(bytes: &[u8], alphabets: &[char]) -> u128 {
+    let mut total = 0u128;
+    let mut carry: Vec<u8> = Vec::new();
+    for window in bytes.chunks(CHUNK_SIZE) {
+        let buf = if carry.is_empty() {
+            window.to_vec()
+        } else {
+            let mut buf = std::mem::take(&mut carry);
+            buf.extend_from_slice(window);
+            buf
+        };
+        let (count, tail) = count_in_bytes(&buf, alphabets);
+        total += count;
+        carry = tail.to_vec();
+    }
+    // Any leftover tail is incomplete at EOF; count whatever is valid.
+    if !carry.is_empty() {
+        total += count_in_bytes(&carry, alphabets).0;
+    }
+    total
+}
+
+/// Advance `idx` forward to the next UTF-8 character boundary so split points
+/// never land in the middle of a multi-byte character.
+fn char_boundary(bytes: &[u8], mut idx: usize) -> usize {
+    while idx < bytes.len() && (bytes[idx] & 0b1100_0000) == 0b1000_0000 {
+        idx += 1;
+    }
+    idx.min(bytes.len())
+}
+
+/// Split a byte slice into boundary-aligned chunks and count them on the rayon
+/// pool. Aligning to character boundaries means no chunk straddles a character.
+fn count_bytes_parallel(bytes: &[u8], alphabets: &[char]) -> FastyResult<u128> {
+    let workers = default_workers();
+    let step = (bytes.len() / workers).max(1);
+
+    let mut bounds = vec![0usize];
+    let mut pos = step;
+    while pos < bytes.len() {
+        let aligned = char_boundary(bytes, pos);
+        if aligned > *bounds.last().unwrap() {
+            bounds.push(aligned);
+        }
+        pos += step;
+    }
+    bounds.push(bytes.len());
+
+    let total = bounds
+        .windows(2)
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|pair| count_in_bytes(&bytes[pair[0]..pair[1]], alphabets).0)
+        .sum();
+    Ok(total)
+}
+
+/// Count how often each whitespace-delimited word appears inside the imported file.
+pub fn count_words(file_path: &str) -> FastyResult<HashMap<String, u64>> {
     let file_text = fs::read_to_string(file_path)?;
-    let counts = file_text.matches(alphabets).count();
-    Ok(counts as u128)
+    let mut counts = HashMap::new();
+    for word in file_text.split_whitespace() {
+        *counts.entry(word.to_owned()).or_insert(0) += 1;
+    }
+    Ok(counts)
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -31,44 +178,67 @@ pub fn count_multiple_seq(file_paths: &[String], alphabets: &[char]) -> FastyRes
     Ok(CountOutput { counts, elapsed })
 }
 
-/// Count alphabets on multiple files (concurrently)
+/// The number of logical CPUs, falling back to a single worker when unknown.
+fn default_workers() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Greedily bin-pack `file_paths` into `workers` buckets of roughly equal total
+/// bytes, so heavy files don't all land on the same worker.
+fn partition_by_bytes(file_paths: Vec<String>, workers: usize) -> Vec<Vec<String>> {
+    let workers = workers.max(1);
+
+    // Stat each path once, then place the largest files first.
+    let mut sized: Vec<(u64, String)> = file_paths
+        .into_iter()
+        .map(|path| {
+            let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            (bytes, path)
+        })
+        .collect();
+    sized.sort_unstable_by_key(|(bytes, _)| Reverse(*bytes));
+
+    let mut buckets: Vec<Vec<String>> = vec![Vec::new(); workers];
+    let mut loads = vec![0u64; workers];
+    for (bytes, path) in sized {
+        // Drop the next file onto the currently lightest bucket.
+        let lightest = loads
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &load)| load)
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+        buckets[lightest].push(path);
+        loads[lightest] += bytes;
+    }
+    buckets
+}
+
+/// Count alphabets on multiple files (concurrently).
+///
+/// Work is partitioned by summed file byte size and dispatched over a rayon
+/// work-stealing pool. `workers` defaults to the number of logical CPUs.
 pub fn count_multiple_concurrent(
     file_paths: Vec<String>,
     alphabets: Vec<char>,
+    workers: Option<usize>,
 ) -> FastyResult<CountOutput> {
     let now = Instant::now(); // Timer start!
 
-    const N_THREADS: usize = 8;
-    let paths_count = file_paths.iter().count();
-    let chunk_size = if paths_count % N_THREADS > 0 {
-        paths_count / N_THREADS + 1
-    } else {
-        paths_count / N_THREADS
-    };
-    let worklists: Vec<_> = file_paths
-        .chunks(chunk_size)
-        .map(|chunk| chunk.to_owned())
-        .collect();
+    let workers = workers.unwrap_or_else(default_workers);
+    let buckets = partition_by_bytes(file_paths, workers);
 
-    // Fork: Spawn a thread to handle each chunk
-    let counts = worklists
-        .into_iter()
-        .map(move |file_paths| {
-            let alphabets = Arc::new(alphabets.clone());
-            thread::spawn(move || -> FastyResult<u128> {
-                let alphabets = alphabets.clone();
-                let result = file_paths
-                    .iter()
-                    .map(|file_path| {
-                        let file_text = fs::read_to_string(file_path)?;
-                        let counts = file_text.matches(alphabets.as_slice()).count();
-                        Ok(counts as u128)
-                    })
-                    .collect::<FastyResult<Vec<_>>>()?;
-                Ok(result.iter().sum())
-            })
+    let counts = buckets
+        .par_iter()
+        .map(|file_paths| -> FastyResult<u128> {
+            let mut local = 0u128;
+            for file_path in file_paths {
+                local += count_alpha_seq(file_path, &alphabets)?;
+            }
+            Ok(local)
         })
-        .map(|handle| handle.join().unwrap())
         .collect::<FastyResult<Vec<_>>>()?
         .iter()
         .sum();
@@ -78,10 +248,212 @@ pub fn count_multiple_concurrent(
     Ok(CountOutput { counts, elapsed })
 }
 
+/// Count alphabets on multiple files with an overlapping IO/compute pipeline.
+///
+/// A reader thread opens files and streams their bytes over a channel, a pool of
+/// `workers` counter threads turn buffers into partial counts, and the merger
+/// sums partials as they arrive — so disk and CPU stay busy simultaneously
+/// instead of alternating per file. `workers` defaults to the logical CPU count.
+pub fn count_multiple_concurrent_pipelined(
+    file_paths: Vec<String>,
+    alphabets: Vec<char>,
+    workers: Option<usize>,
+) -> FastyResult<CountOutput> {
+    let now = Instant::now(); // Timer start!
+
+    let workers = workers.unwrap_or_else(default_workers);
+    let (file_tx, file_rx) = mpsc::channel::<FastyResult<Vec<u8>>>();
+    let (count_tx, count_rx) = mpsc::channel::<FastyResult<u128>>();
+
+    // Reader stage: open each file and send its contents downstream.
+    let reader = thread::spawn(move || {
+        for path in file_paths {
+            if file_tx.send(fs::read(&path)).is_err() {
+                break; // all counters gone, no point reading further
+            }
+        }
+    });
+
+    // Counter stage: a pool pulls buffers and produces partial counts.
+    let file_rx = Arc::new(Mutex::new(file_rx));
+    let alphabets = Arc::new(alphabets);
+    let counters: Vec<_> = (0..workers)
+        .map(|_| {
+            let file_rx = Arc::clone(&file_rx);
+            let count_tx = count_tx.clone();
+            let alphabets = Arc::clone(&alphabets);
+            thread::spawn(move || loop {
+                let msg = file_rx.lock().unwrap().recv();
+                match msg {
+                    Ok(Ok(bytes)) => {
+                        let partial = count_bytes_sequential(&bytes, &alphabets);
+                        let _ = count_tx.send(Ok(partial));
+                    }
+                    Ok(Err(e)) => {
+                        let _ = count_tx.send(Err(e));
+                    }
+                    Err(_) => break, // reader finished, channel drained
+                }
+            })
+        })
+        .collect();
+    drop(count_tx); // merger stops once every counter's sender is dropped
+
+    // Merger stage: sum partials as they arrive, remembering the first error.
+    let mut counts = 0u128;
+    let mut first_err = None;
+    for msg in count_rx {
+        match msg {
+            Ok(partial) => counts += partial,
+            Err(e) => {
+                if first_err.is_none() {
+                    first_err = Some(e);
+                }
+            }
+        }
+    }
+
+    reader.join().unwrap();
+    for counter in counters {
+        counter.join().unwrap();
+    }
+
+    if let Some(e) = first_err {
+        return Err(e);
+    }
+
+    let elapsed = now.elapsed().as_micros(); // Timer ends.
+
+    Ok(CountOutput { counts, elapsed })
+}
+
+/// Recursively gather every regular file under `dir`, skipping any whose
+/// `(device, inode)` node id was already seen so hardlinked files aren't
+/// double-counted.
+fn collect_dedup_files(
+    dir: &Path,
+    seen: &Arc<Mutex<HashSet<(u64, u64)>>>,
+    files: &mut Vec<String>,
+) -> FastyResult<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        let file_type = meta.file_type();
+        if file_type.is_dir() {
+            collect_dedup_files(&entry.path(), seen, files)?;
+        } else if file_type.is_file() {
+            let node = (meta.dev(), meta.ino());
+            if seen.lock().unwrap().insert(node) {
+                let path = entry.path();
+                // The counting pipeline is UTF-8 path based; don't silently drop
+                // a file whose name isn't valid UTF-8 — surface it instead.
+                let path = path.to_str().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("non-UTF-8 path: {}", path.display()),
+                    )
+                })?;
+                files.push(path.to_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recursively count alphabets across every regular file under `dir_path`.
+///
+/// Hardlinked files are counted once: each file's `(device, inode)` is recorded
+/// in a shared set guarded by a mutex, and any node already seen is skipped.
+/// `workers` defaults to the number of logical CPUs.
+pub fn count_dir(
+    dir_path: &str,
+    alphabets: Vec<char>,
+    workers: Option<usize>,
+) -> FastyResult<CountOutput> {
+    let seen = Arc::new(Mutex::new(HashSet::new()));
+    let mut files = Vec::new();
+    collect_dedup_files(Path::new(dir_path), &seen, &mut files)?;
+    count_multiple_concurrent(files, alphabets, workers)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WordCountOutput {
+    counts: HashMap<String, u64>,
+    elapsed: u128,
+}
+
+impl WordCountOutput {
+    /// Serialize the frequency table as `{"word": count, ...}` by hand (no serde).
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .counts
+            .iter()
+            .map(|(word, count)| format!("\"{}\":{}", escape_json(word), count))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{{entries}}}")
+    }
+}
+
+/// Escape the characters that must be quoted inside a JSON string literal.
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Other control characters must be emitted as \u00XX to stay valid JSON.
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Count word frequencies on multiple files (concurrently).
+///
+/// Work is partitioned by summed file byte size and dispatched over a rayon
+/// work-stealing pool. Each worker builds a local `HashMap` over its bucket of
+/// files, and the join section merges them by summing counts for shared keys.
+pub fn count_words_multiple_concurrent(file_paths: Vec<String>) -> FastyResult<WordCountOutput> {
+    let now = Instant::now(); // Timer start!
+
+    let buckets = partition_by_bytes(file_paths, default_workers());
+
+    // Fork: each worker folds its bucket into a local frequency map.
+    let partials = buckets
+        .par_iter()
+        .map(|file_paths| -> FastyResult<HashMap<String, u64>> {
+            let mut counts = HashMap::new();
+            for file_path in file_paths {
+                let file_text = fs::read_to_string(file_path)?;
+                for word in file_text.split_whitespace() {
+                    *counts.entry(word.to_owned()).or_insert(0) += 1;
+                }
+            }
+            Ok(counts)
+        })
+        .collect::<FastyResult<Vec<_>>>()?;
+
+    // Join: merge the per-worker maps by summing counts for shared keys.
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for partial in partials {
+        for (word, count) in partial {
+            *counts.entry(word).or_insert(0) += count;
+        }
+    }
+
+    let elapsed = now.elapsed().as_micros(); // Timer ends.
+
+    Ok(WordCountOutput { counts, elapsed })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use lipsum::lipsum_words;
     use std::fs;
 
     #[test]
@@ -106,10 +478,9 @@ mod test {
     fn should_count_on_multiple_files_sequentially() {
         // Arrange
         let file_paths: Vec<String> = (0..16).map(|num| format!("data/{num}.txt")).collect();
-        let words = 100000;
         file_paths.iter().for_each(|file_path| {
-            let content = lipsum_words(words);
-            fs::write(file_path, content).unwrap();
+            // "abcabc" holds two 'a's and two 'c's -> four matches per file.
+            fs::write(file_path, "abcabc").unwrap();
         });
         let alphabets = vec!['a', 'c'];
         // Act
@@ -119,40 +490,136 @@ mod test {
             fs::remove_file(file_path).unwrap();
         });
         // Assert
-        assert_eq!(
-            CountOutput {
-                counts: 1000,
-                elapsed: 1000
-            },
-            result
-        );
-        assert!(matches!(result, CountOutput { .. }));
+        assert_eq!(4 * 16, result.counts);
     }
 
     #[test]
     fn should_count_on_multiple_files_concurrently() {
         // Arrange
         let file_paths: Vec<String> = (0..16).map(|num| format!("data/{num}.txt")).collect();
-        let words = 100000;
         file_paths.iter().for_each(|file_path| {
-            let content = lipsum_words(words);
-            fs::write(file_path, content).unwrap();
+            // "abcabc" holds two 'a's and two 'c's -> four matches per file.
+            fs::write(file_path, "abcabc").unwrap();
         });
         let alphabets = vec!['a', 'c'];
         // Act
-        let result = count_multiple_concurrent(file_paths.clone(), alphabets).unwrap();
+        let result = count_multiple_concurrent(file_paths.clone(), alphabets, None).unwrap();
         // remove generated txt files
         file_paths.iter().for_each(|file_path| {
             fs::remove_file(file_path).unwrap();
         });
+        // Assert: four matches per file across all sixteen files.
+        assert_eq!(4 * 16, result.counts);
+    }
+
+    #[test]
+    fn should_count_word_frequencies_and_render_json() {
+        // Arrange
+        let file_paths: Vec<String> = (0..4).map(|num| format!("data/words{num}.txt")).collect();
+        file_paths.iter().for_each(|file_path| {
+            fs::write(file_path, "fasty read fasty").unwrap();
+        });
+        // Act
+        let result = count_words_multiple_concurrent(file_paths.clone()).unwrap();
+        // remove generated txt files
+        file_paths.iter().for_each(|file_path| {
+            fs::remove_file(file_path).unwrap();
+        });
+        // Assert
+        assert_eq!(Some(&8), result.counts.get("fasty"));
+        assert_eq!(Some(&4), result.counts.get("read"));
+        let json = result.to_json();
+        assert!(json.contains("\"fasty\":8"));
+        assert!(json.contains("\"read\":4"));
+    }
+
+    #[test]
+    fn should_count_multibyte_char_straddling_chunk_boundary() {
+        // Arrange: place a 2-byte 'é' so its bytes span the 1 MiB seam.
+        let file_path = "data/seam.txt";
+        let mut content = "a".repeat(CHUNK_SIZE - 1);
+        content.push('é');
+        content.push('a');
+        fs::write(file_path, &content).unwrap();
+        // Act
+        let a_count = count_alpha(file_path, &['a']).unwrap();
+        let e_count = count_alpha(file_path, &['é']).unwrap();
+        // remove generated txt file
+        fs::remove_file(file_path).unwrap();
+        // Assert
+        assert_eq!(CHUNK_SIZE as u128, a_count);
+        assert_eq!(1, e_count);
+    }
+
+    #[test]
+    fn should_skip_invalid_bytes_and_count_the_rest() {
+        // Arrange
+        let file_path = "data/mixed.bin";
+        fs::write(file_path, b"abc\xFFac").unwrap();
+        // Act
+        let result = count_alpha(file_path, &['a', 'c']).unwrap();
+        // remove generated file
+        fs::remove_file(file_path).unwrap();
         // Assert
-        assert_eq!(
-            CountOutput {
-                counts: 1000,
-                elapsed: 1000
-            },
-            result
+        assert_eq!(4, result);
+    }
+
+    #[test]
+    fn should_count_multi_mib_non_utf8_file_without_overflow() {
+        // Arrange: a binary blob larger than the parallel threshold.
+        let file_path = "data/binary.bin";
+        fs::write(file_path, vec![0xFFu8; 8 << 20]).unwrap();
+        // Act
+        let result = count_alpha(file_path, &['a', 'c']).unwrap();
+        // remove generated file
+        fs::remove_file(file_path).unwrap();
+        // Assert
+        assert_eq!(0, result);
+    }
+
+    #[test]
+    fn should_count_on_multiple_files_with_pipeline() {
+        // Arrange
+        let file_paths: Vec<String> = (0..6).map(|num| format!("data/pipe{num}.txt")).collect();
+        file_paths.iter().for_each(|file_path| {
+            fs::write(file_path, "fasty read fasty\n").unwrap();
+        });
+        // Act
+        let result = count_multiple_concurrent_pipelined(file_paths.clone(), vec!['a'], None).unwrap();
+        // remove generated txt files
+        file_paths.iter().for_each(|file_path| {
+            fs::remove_file(file_path).unwrap();
+        });
+        // Assert: three 'a's per file, summed across all six partials.
+        assert_eq!(18, result.counts);
+    }
+
+    #[test]
+    fn should_surface_read_error_from_pipeline() {
+        // Act: a path that does not exist must propagate as an error.
+        let result = count_multiple_concurrent_pipelined(
+            vec!["data/missing_pipe.txt".to_string()],
+            vec!['a'],
+            None,
         );
-        // assert!(matches!(result, CountOutput { .. }));
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_count_hardlinked_file_once() {
+        // Arrange: a file plus a hardlink to it under the same tree.
+        let dir = "data/hardlinks";
+        fs::create_dir_all(dir).unwrap();
+        let original = format!("{dir}/original.txt");
+        let link = format!("{dir}/link.txt");
+        fs::write(&original, "aaa").unwrap();
+        fs::hard_link(&original, &link).unwrap();
+        // Act
+        let result = count_dir(dir, vec!['a'], None).unwrap();
+        // remove generated tree
+        fs::remove_dir_all(dir).unwrap();
+        // Assert: both entries share one (dev, inode), so counted once.
+        assert_eq!(3, result.counts);
     }
 }